@@ -0,0 +1,66 @@
+// Benchmark evidence for why `Position::transpose` stays a plain scalar nested loop.
+//
+// chunk0-4 originally asked for a SIMD (`_mm_shuffle_epi8`) transpose for a "large throughput
+// win"; that was tried, measured slower, and dropped, but with no numbers to back the claim. This
+// re-checks the question with two scalar bit-twiddling candidates that avoid the SIMD-vs-scalar
+// domain switch entirely (swap the 6 off-diagonal nibble pairs directly in the packed u64) and
+// times them against `Position::transpose` itself. Run with `cargo run --release --example
+// transpose_bench`.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use learning_rust::Position;
+
+const ITERS: u64 = 20_000_000;
+
+// Off-diagonal nibble-index pairs in a 4x4 board packed 4 bits/tile (indices 0 and 5, 10, 15 are
+// on the diagonal and never move).
+const PAIRS: [(u32, u32); 6] = [(1, 4), (2, 8), (3, 12), (6, 9), (7, 13), (11, 14)];
+
+// Six sequential XOR-swaps, one pair at a time.
+fn transpose_xor_chain(x: u64) -> u64 {
+    let mut x = x;
+    for &(i, j) in &PAIRS {
+        let (si, sj) = (4 * i, 4 * j);
+        let t = ((x >> si) ^ (x >> sj)) & 0xF;
+        x ^= (t << si) | (t << sj);
+    }
+    x
+}
+
+// Same swaps, but every diff is computed from the original word and combined with one final XOR,
+// so the six pairs don't form a dependency chain.
+fn transpose_xor_parallel(x: u64) -> u64 {
+    let mut diff = 0u64;
+    for &(i, j) in &PAIRS {
+        let (si, sj) = (4 * i, 4 * j);
+        let t = ((x >> si) ^ (x >> sj)) & 0xF;
+        diff ^= (t << si) | (t << sj);
+    }
+    x ^ diff
+}
+
+fn bench(name: &str, mut f: impl FnMut(u64) -> u64) {
+    let mut x = 0x0123_4567_89ab_cdefu64;
+
+    let start = Instant::now();
+    for _ in 0..ITERS {
+        x = black_box(f(black_box(x)));
+    }
+    let elapsed = start.elapsed();
+    black_box(x);
+
+    println!(
+        "{name}: {elapsed:?} for {ITERS} calls ({:.1} ns/call)",
+        elapsed.as_nanos() as f64 / ITERS as f64
+    );
+}
+
+fn main() {
+    bench("transpose (current, nested loop over exponent_at/set_exponent)", |x| {
+        Position::from_u64(x).unwrap().transpose().to_u64()
+    });
+    bench("transpose_xor_chain (sequential nibble swaps)", transpose_xor_chain);
+    bench("transpose_xor_parallel (independent diffs, one final XOR)", transpose_xor_parallel);
+}
@@ -0,0 +1,661 @@
+// use std::arch::x86_64;
+
+use std::cmp::max;
+use std::ops::{Index, IndexMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::fmt;
+
+use arrayvec::ArrayVec;
+
+pub mod search;
+
+// We store a position packed 4 bits per tile, so the whole 4x4 board fits in a single u64. We
+// order the nibbles sequentially in memory, so that the lowest nibble of Position (p.0 & 0xF) is
+// the exponent of the top left tile, reading left-to-right then top-to-bottom. An exponent of 0,
+// of course, means an empty tile. We distinguish between exponents and tiles; the 0 tile has an
+// exponent of 0, the 2 tile has an exponent of 1, et cetera. Exponents are stored as packed nibbles,
+// while tiles are stored as u32s. The 16-byte alignment buys us room for a future SIMD code path
+// that loads the whole board into a single vector register.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C, align(16))]
+pub struct Position(u64);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParsePositionError {
+    WrongLength(usize),
+    InvalidDigit(char),
+    InvalidExponent(u8),
+}
+
+impl fmt::Display for ParsePositionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParsePositionError::WrongLength(len) => write!(f, "expected 16 tiles, found {len}"),
+            ParsePositionError::InvalidDigit(c) => write!(f, "invalid base-36 digit '{c}'"),
+            ParsePositionError::InvalidExponent(e) => write!(f, "invalid exponent {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ParsePositionError {}
+
+// A precomputed move-left result for one row: the post-slide row and the score gained from
+// merges within it.
+struct RowMove {
+    row: u16,
+    score: u32,
+}
+
+fn row_move_tables() -> &'static (Vec<u16>, Vec<u32>) {
+    static TABLES: OnceLock<(Vec<u16>, Vec<u32>)> = OnceLock::new();
+
+    TABLES.get_or_init(|| {
+        let mut rows = vec![0u16; 65536];
+        let mut scores = vec![0u32; 65536];
+
+        for packed in 0..65536usize {
+            let mv = compute_row_move_left(packed as u16);
+            rows[packed] = mv.row;
+            scores[packed] = mv.score;
+        }
+
+        (rows, scores)
+    })
+}
+
+// Slides a single row (4 nibbles) to the left: drop zero exponents, merge each adjacent equal
+// pair exactly once (left to right), then pad with zeros on the right. Two tiles already at the
+// maximum representable exponent are left unmerged rather than overflowing into the next nibble.
+fn compute_row_move_left(row: u16) -> RowMove {
+    let mut exponents: Vec<u8> = (0..4)
+        .map(|i| ((row >> (4 * i)) & 0xF) as u8)
+        .filter(|&e| e != 0)
+        .collect();
+
+    let mut merged = Vec::with_capacity(4);
+    let mut score = 0u32;
+    let mut i = 0;
+
+    while i < exponents.len() {
+        let can_merge = i + 1 < exponents.len()
+            && exponents[i] == exponents[i + 1]
+            && exponents[i] < MAX_EXPONENT;
+
+        if can_merge {
+            let e = exponents[i] + 1;
+            merged.push(e);
+            score += exponent_to_tile(e);
+            i += 2;
+        } else {
+            merged.push(exponents[i]);
+            i += 1;
+        }
+    }
+
+    merged.resize(4, 0);
+
+    let mut out = 0u16;
+    for (i, &e) in merged.iter().enumerate() {
+        out |= (e as u16) << (4 * i);
+    }
+
+    RowMove { row: out, score }
+}
+
+// Reverses the order of the 4 nibbles in a row, used to turn a right slide into a left slide.
+fn reverse_row(row: u16) -> u16 {
+    let mut out = 0u16;
+    for i in 0..4 {
+        let nibble = (row >> (4 * i)) & 0xF;
+        out |= nibble << (4 * (3 - i));
+    }
+    out
+}
+
+fn exponent_to_tile(e: u8) -> u32 {
+    if e == 0 {
+        0
+    } else {
+        1u32 << e
+    }
+}
+
+// Exponents are packed 4 bits per tile, so the representable range is 0-15 (tile values up to
+// 2^15 = 32768); anything higher would overflow into the neighboring nibble.
+const MAX_EXPONENT: u8 = 15;
+
+fn is_valid_exponent(e: u8) -> bool {
+    e < 16
+}
+
+// Checks if exponent is in range and if it's a power of two
+fn is_valid_tile(tile: u32) -> bool {
+    let e = tile_to_exponent(tile);
+
+    is_valid_exponent(e) && exponent_to_tile(e) == tile
+}
+
+// Convert a tile to an exponent; technically converts via a floored log, so 8,9 -> 4
+fn tile_to_exponent(tile: u32) -> u8 {
+    if tile == 0 {
+        0
+    } else {
+        (31 - tile.leading_zeros()) as u8
+    }
+}
+
+pub struct RNG {
+    state: u64
+}
+
+impl RNG {
+    pub fn next_u64(&mut self) -> u64 {
+        const M: u64 = 6364136223846793005;
+        const I: u64 = 1;
+
+        self.state = u64::wrapping_add(u64::wrapping_mul(M, self.state), I);
+
+        // The low bits of an LCG are low-quality, so xorshift-mix the state before returning it.
+        let mut x = self.state;
+        x ^= x >> 33;
+        x = x.wrapping_mul(0xff51afd7ed558ccd);
+        x ^= x >> 33;
+        x
+    }
+
+    pub fn from_seed(seed: u64) -> RNG {
+        RNG { state: seed }
+    }
+}
+
+impl Position {
+    // Get the exponent at a particular row and column
+    pub fn exponent_at(&self, row: usize, col: usize) -> u8 {
+        let shift = 4 * (row * 4 + col);
+
+        ((self.0 & (0xFu64 << shift)) >> shift) as u8
+    }
+
+    pub fn tile_at(&self, row: usize, col: usize) -> u32 {
+        exponent_to_tile(self.exponent_at(row, col))
+    }
+
+    pub fn set_exponent(&mut self, row: usize, col: usize, e: u8) {
+        let shift = 4 * (row * 4 + col);
+        let mask = 0xFu64 << shift;
+
+        self.0 = (self.0 & !mask) | ((0xFu64 & (e as u64)) << shift);
+    }
+
+    pub fn set_tile(&mut self, row: usize, col: usize, tile: u32) {
+        self.set_exponent(row, col, tile_to_exponent(tile));
+    }
+
+    // Panics if the position is invalid
+    pub fn validate_position(&self) {
+        for row in 0..4usize {
+            for col in 0..4usize {
+                let e = self.exponent_at(row, col);
+
+                if !is_valid_exponent(e) {
+                    panic!("Invalid exponent {e} at row {row} and column {col}", e=e, row=row, col=col);
+                }
+            }
+        }
+    }
+
+    pub fn from_list(list: [u32; 16]) -> Position {
+        let mut p = Position(0);
+
+        for row in 0..4 {
+            for col in 0..4 {
+                let i = row * 4 + col;
+                let tile = list[i];
+
+                if !is_valid_tile(tile) {
+                    panic!("Invalid tile {tile} at index {i}", tile=tile, i=i);
+                }
+
+                p.set_tile(row, col, tile);
+            }
+        }
+
+        p
+    }
+
+    pub fn to_string(&self) -> String {
+        // Build a 4x4 array of strings
+        let mut strs: [[String; 4]; 4] = Default::default();
+        // Maximum width of each column
+        let mut widths = [1usize; 4];
+
+        for row in 0..4usize {
+            for col in 0..4usize {
+                strs[row][col] = self.tile_at(row, col).to_string();
+
+                widths[col] = max(widths[col], strs[row][col].len());
+            }
+        }
+
+        // Join the strings
+        let mut out = String::new();
+
+        for row in 0..4usize {
+            for col in 0..4usize {
+                let tile = &strs[row][col];
+
+                // pad left with spaces
+                out.push_str(&" ".repeat(widths[col] - tile.len()));
+                out.push_str(tile);
+
+                if col < 3 {
+                    // column spacing
+                    out += " ";
+                }
+            }
+
+            out += "\n";
+        }
+
+        out
+    }
+
+    pub fn from_string(s: &str) -> Position {
+        // Split s across whitespace
+        let mut split = s.split_whitespace();
+        let mut exponents = Vec::new();
+
+        for (i, s) in split.enumerate() {
+            let tile: u32 = s.parse().unwrap_or_else(|_| {
+                panic!("Invalid tile {tile}", tile = s)
+            });
+
+            if !is_valid_tile(tile) {
+                panic!("Invalid tile {tile} at index {i}", tile = tile, i = i)
+            }
+
+            exponents.push(tile_to_exponent(tile));
+        }
+
+        if exponents.len() != 16 {
+            panic!("{count} tiles found (should be 16)", count = exponents.len())
+        }
+
+        let mut p = Position(0);
+
+        for row in 0..4 {
+            for col in 0..4 {
+                p.set_exponent(row, col, exponents[row * 4 + col]);
+            }
+        }
+
+        p
+    }
+
+    // The packed 4-bit-per-tile board, useful as a transposition-table key or for logging.
+    pub fn to_u64(self) -> u64 {
+        self.0
+    }
+
+    pub fn from_u64(value: u64) -> Result<Position, ParsePositionError> {
+        for i in 0..16 {
+            let e = ((value >> (4 * i)) & 0xF) as u8;
+
+            if !is_valid_exponent(e) {
+                return Err(ParsePositionError::InvalidExponent(e));
+            }
+        }
+
+        Ok(Position(value))
+    }
+
+    // A fixed 16-character code: one base-36 digit per cell's exponent, reading left-to-right
+    // then top-to-bottom. Compact and round-trippable, so it's handy for test fixtures.
+    pub fn to_code(self) -> String {
+        (0..16)
+            .map(|i| {
+                let e = ((self.0 >> (4 * i)) & 0xF) as u32;
+                char::from_digit(e, 36).unwrap()
+            })
+            .collect()
+    }
+
+    pub fn from_code(s: &str) -> Result<Position, ParsePositionError> {
+        let chars: Vec<char> = s.chars().collect();
+
+        if chars.len() != 16 {
+            return Err(ParsePositionError::WrongLength(chars.len()));
+        }
+
+        let mut p = Position(0);
+
+        for (i, &c) in chars.iter().enumerate() {
+            let digit = c.to_digit(36).ok_or(ParsePositionError::InvalidDigit(c))?;
+
+            // `to_digit(36)` accepts digits up to 35, wider than the 4-bit nibble a code digit
+            // maps to, so check the range before truncating rather than after.
+            if digit >= 16 {
+                return Err(ParsePositionError::InvalidExponent(digit as u8));
+            }
+
+            let e = digit as u8;
+
+            if !is_valid_exponent(e) {
+                return Err(ParsePositionError::InvalidExponent(e));
+            }
+
+            p.set_exponent(i / 4, i % 4, e);
+        }
+
+        Ok(p)
+    }
+
+    // Swaps rows and columns, so that the tile at (row, col) moves to (col, row). Used to turn
+    // vertical moves into horizontal ones so they can reuse the row-move tables.
+    pub fn transpose(&self) -> Position {
+        let mut out = Position(0);
+
+        for row in 0..4usize {
+            for col in 0..4usize {
+                out.set_exponent(col, row, self.exponent_at(row, col));
+            }
+        }
+
+        out
+    }
+
+    fn row(&self, row: usize) -> u16 {
+        ((self.0 >> (16 * row)) & 0xFFFF) as u16
+    }
+
+    fn set_row(&mut self, row: usize, value: u16) {
+        let shift = 16 * row;
+        self.0 = (self.0 & !(0xFFFFu64 << shift)) | ((value as u64) << shift);
+    }
+
+    // Slides every row to the left, merging adjacent equal tiles once each. Returns whether the
+    // board changed and the score gained from merges.
+    pub fn move_left(&mut self) -> (bool, u32) {
+        let (rows, scores) = row_move_tables();
+        let mut changed = false;
+        let mut score = 0;
+
+        for r in 0..4 {
+            let cur = self.row(r);
+            let new = rows[cur as usize];
+
+            if new != cur {
+                changed = true;
+                self.set_row(r, new);
+            }
+
+            score += scores[cur as usize];
+        }
+
+        (changed, score)
+    }
+
+    // Mirror image of move_left: reverse each row, look it up, then reverse the result back.
+    pub fn move_right(&mut self) -> (bool, u32) {
+        let (rows, scores) = row_move_tables();
+        let mut changed = false;
+        let mut score = 0;
+
+        for r in 0..4 {
+            let cur = reverse_row(self.row(r));
+            let new = rows[cur as usize];
+
+            score += scores[cur as usize];
+
+            let new = reverse_row(new);
+            if new != self.row(r) {
+                changed = true;
+                self.set_row(r, new);
+            }
+        }
+
+        (changed, score)
+    }
+
+    // Transpose, slide left (columns become rows), then transpose back.
+    pub fn move_up(&mut self) -> (bool, u32) {
+        let mut t = self.transpose();
+        let result = t.move_left();
+        *self = t.transpose();
+        result
+    }
+
+    // Transpose, slide right, then transpose back.
+    pub fn move_down(&mut self) -> (bool, u32) {
+        let mut t = self.transpose();
+        let result = t.move_right();
+        *self = t.transpose();
+        result
+    }
+
+    pub fn apply_move(&mut self, dir: Direction) -> (bool, u32) {
+        match dir {
+            Direction::Left => self.move_left(),
+            Direction::Right => self.move_right(),
+            Direction::Up => self.move_up(),
+            Direction::Down => self.move_down(),
+        }
+    }
+
+    // Spawns a 2 (90% of the time) or a 4 (10% of the time) on a uniformly random empty cell.
+    // Returns false if the board has no empty cells to spawn into.
+    pub fn spawn_random_tile(&mut self, rng: &mut RNG) -> bool {
+        let empty: Vec<(usize, usize)> = (0..4)
+            .flat_map(|row| (0..4).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.exponent_at(row, col) == 0)
+            .collect();
+
+        if empty.is_empty() {
+            return false;
+        }
+
+        let (row, col) = empty[(rng.next_u64() % empty.len() as u64) as usize];
+        let e = if rng.next_u64() % 10 == 0 { 2 } else { 1 };
+
+        self.set_exponent(row, col, e);
+
+        true
+    }
+
+    // Starts a fresh game: an empty board with two random tiles spawned on it.
+    pub fn new_game(rng: &mut RNG) -> Position {
+        let mut p = Position(0);
+
+        p.spawn_random_tile(rng);
+        p.spawn_random_tile(rng);
+
+        p
+    }
+
+    // The game is over when no direction is legal: the board is full and no two horizontally or
+    // vertically adjacent tiles share an exponent, so nothing could slide or merge.
+    pub fn is_game_over(&self) -> bool {
+        for row in 0..4usize {
+            for col in 0..4usize {
+                let e = self.exponent_at(row, col);
+
+                if e == 0 {
+                    return false;
+                }
+
+                if col + 1 < 4 && self.exponent_at(row, col + 1) == e {
+                    return false;
+                }
+
+                if row + 1 < 4 && self.exponent_at(row + 1, col) == e {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    // The directions that would actually change the board if applied.
+    pub fn legal_moves(&self) -> ArrayVec<Direction, 4> {
+        let mut moves = ArrayVec::new();
+
+        for &dir in &[Direction::Left, Direction::Right, Direction::Up, Direction::Down] {
+            let mut next = *self;
+            let (changed, _) = next.apply_move(dir);
+
+            if changed {
+                moves.push(dir);
+            }
+        }
+
+        moves
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_exponent_overwrites_rather_than_ors() {
+        let mut p = Position::from_list([4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        p.set_exponent(0, 0, 1);
+
+        assert_eq!(p.exponent_at(0, 0), 1);
+    }
+
+    #[test]
+    fn move_left_merges_each_pair_once() {
+        // 2 2 2 2 -> 4 4 0 0, not 8 0 0 0: a freshly merged tile can't merge again this move.
+        let mut p = Position::from_list([2, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        let (changed, score) = p.move_left();
+
+        assert!(changed);
+        assert_eq!(score, 8);
+        assert_eq!([p.tile_at(0, 0), p.tile_at(0, 1), p.tile_at(0, 2), p.tile_at(0, 3)], [4, 4, 0, 0]);
+    }
+
+    #[test]
+    fn move_left_slides_without_merging_unequal_tiles() {
+        let mut p = Position::from_list([0, 2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        let (changed, score) = p.move_left();
+
+        assert!(changed);
+        assert_eq!(score, 0);
+        assert_eq!([p.tile_at(0, 0), p.tile_at(0, 1), p.tile_at(0, 2), p.tile_at(0, 3)], [2, 4, 0, 0]);
+    }
+
+    #[test]
+    fn move_left_reports_no_change_when_already_slid() {
+        let mut p = Position::from_list([2, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        let (changed, _) = p.move_left();
+
+        assert!(!changed);
+    }
+
+    #[test]
+    fn merging_two_max_exponent_tiles_does_not_overflow_into_the_next_tile() {
+        // Two tiles at the maximum representable nibble exponent can't merge into exponent 16,
+        // so they should be left as-is rather than corrupting a neighboring cell.
+        let mut p = Position(0);
+        p.set_exponent(0, 0, MAX_EXPONENT);
+        p.set_exponent(0, 1, MAX_EXPONENT);
+
+        let (changed, score) = p.move_left();
+
+        assert!(!changed);
+        assert_eq!(score, 0);
+        assert_eq!(p.exponent_at(0, 0), MAX_EXPONENT);
+        assert_eq!(p.exponent_at(0, 1), MAX_EXPONENT);
+        assert_eq!(p.exponent_at(0, 2), 0);
+    }
+
+    #[test]
+    fn move_up_and_down_transpose_correctly() {
+        let mut p = Position::from_list([
+            2, 0, 0, 0,
+            2, 0, 0, 0,
+            0, 0, 0, 0,
+            0, 0, 0, 0,
+        ]);
+
+        let (changed, score) = p.move_up();
+
+        assert!(changed);
+        assert_eq!(score, 4);
+        assert_eq!(p.tile_at(0, 0), 4);
+        assert_eq!(p.tile_at(1, 0), 0);
+    }
+
+    #[test]
+    fn to_code_from_code_round_trips() {
+        let p = Position::from_list([2, 4, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 16]);
+
+        let code = p.to_code();
+        let round_tripped = Position::from_code(&code).unwrap();
+
+        assert_eq!(code.len(), 16);
+        assert_eq!(round_tripped.to_u64(), p.to_u64());
+    }
+
+    #[test]
+    fn from_code_rejects_wrong_length() {
+        assert_eq!(Position::from_code("000"), Err(ParsePositionError::WrongLength(3)));
+    }
+
+    #[test]
+    fn from_code_rejects_a_digit_too_wide_for_a_nibble() {
+        // 'g' is base-36 digit 16, one past the largest exponent a nibble can hold.
+        assert_eq!(
+            Position::from_code("g000000000000000"),
+            Err(ParsePositionError::InvalidExponent(16)),
+        );
+    }
+
+    #[test]
+    fn to_u64_from_u64_round_trips() {
+        let p = Position::from_list([2, 4, 8, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 32]);
+
+        let round_tripped = Position::from_u64(p.to_u64()).unwrap();
+
+        assert_eq!(round_tripped.to_u64(), p.to_u64());
+    }
+
+    #[test]
+    fn spawn_random_tile_fills_the_board_and_then_reports_full() {
+        let mut rng = RNG::from_seed(42);
+        let mut p = Position(0);
+
+        for _ in 0..16 {
+            assert!(p.spawn_random_tile(&mut rng));
+        }
+
+        assert!(!p.spawn_random_tile(&mut rng));
+
+        for row in 0..4 {
+            for col in 0..4 {
+                assert!(p.exponent_at(row, col) == 1 || p.exponent_at(row, col) == 2);
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+// Expectimax search over `Position`: alternating max nodes (the player picks a move) and chance
+// nodes (the game spawns a 2 or a 4 on a random empty cell).
+
+use std::collections::HashMap;
+
+use crate::{Direction, Position};
+
+const DIRECTIONS: [Direction; 4] = [Direction::Left, Direction::Right, Direction::Up, Direction::Down];
+
+// Chance branches lighter than this fraction of the root's probability mass aren't worth
+// exploring further.
+const PROBABILITY_PRUNE_THRESHOLD: f64 = 0.0001;
+
+// Transposition table, keyed by the packed board plus remaining depth (the same board can be
+// reached at different depths and those are not interchangeable).
+type TransTable = HashMap<(u64, u32), f64>;
+
+// Returns the move that maximizes expected value under a depth-limited expectimax search, or
+// `None` if no move changes the board (game over).
+pub fn best_move(pos: &Position, depth: u32) -> Option<Direction> {
+    let mut table = TransTable::new();
+    let search_depth = effective_depth(pos, depth);
+
+    DIRECTIONS
+        .iter()
+        .copied()
+        .filter_map(|dir| {
+            let mut next = *pos;
+            let (changed, _) = next.apply_move(dir);
+
+            if !changed {
+                return None;
+            }
+
+            Some((dir, chance_value(&next, search_depth, 1.0, &mut table)))
+        })
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        .map(|(dir, _)| dir)
+}
+
+// Search deeper as the board empties out, since there are fewer plausible chance branches to
+// explore and the remaining moves matter more.
+fn effective_depth(pos: &Position, base_depth: u32) -> u32 {
+    match empty_cells(pos).len() {
+        0..=1 => base_depth + 2,
+        2..=3 => base_depth + 1,
+        _ => base_depth,
+    }
+}
+
+fn max_value(pos: &Position, depth: u32, probability: f64, table: &mut TransTable) -> f64 {
+    if depth == 0 {
+        return evaluate(pos);
+    }
+
+    let best = DIRECTIONS
+        .iter()
+        .copied()
+        .filter_map(|dir| {
+            let mut next = *pos;
+            let (changed, _) = next.apply_move(dir);
+
+            if !changed {
+                return None;
+            }
+
+            Some(chance_value(&next, depth - 1, probability, table))
+        })
+        .fold(None, |acc, v| Some(acc.map_or(v, |a: f64| a.max(v))));
+
+    // No legal move: the game is over at this node.
+    best.unwrap_or_else(|| evaluate(pos))
+}
+
+fn chance_value(pos: &Position, depth: u32, probability: f64, table: &mut TransTable) -> f64 {
+    let empty = empty_cells(pos);
+
+    if depth == 0 || empty.is_empty() {
+        return evaluate(pos);
+    }
+
+    let key = (pos.0, depth);
+    if let Some(&cached) = table.get(&key) {
+        return cached;
+    }
+
+    let per_cell = 1.0 / empty.len() as f64;
+    let mut total = 0.0;
+
+    for &(row, col) in &empty {
+        for &(exponent, weight) in &[(1u8, 0.9), (2u8, 0.1)] {
+            // The probability of reaching this branch compounds across plies, so it's the
+            // accumulated path probability -- not just this branch's local odds -- that's
+            // compared against the pruning threshold.
+            let branch_probability = probability * per_cell * weight;
+
+            // Keep exploring every branch once few tiles remain empty, since that's exactly
+            // when precision matters most.
+            if branch_probability < PROBABILITY_PRUNE_THRESHOLD && empty.len() > 2 {
+                continue;
+            }
+
+            let mut child = *pos;
+            child.set_exponent(row, col, exponent);
+
+            total += per_cell * weight * max_value(&child, depth - 1, branch_probability, table);
+        }
+    }
+
+    table.insert(key, total);
+    total
+}
+
+fn empty_cells(pos: &Position) -> Vec<(usize, usize)> {
+    (0..4)
+        .flat_map(|row| (0..4).map(move |col| (row, col)))
+        .filter(|&(row, col)| pos.exponent_at(row, col) == 0)
+        .collect()
+}
+
+// Static heuristic used at search leaves: rewards open cells, smooth and monotonic boards, and
+// keeping the largest tile in a corner.
+pub fn evaluate(pos: &Position) -> f64 {
+    const EMPTY_WEIGHT: f64 = 2.7;
+    const SMOOTHNESS_WEIGHT: f64 = 0.1;
+    const MONOTONICITY_WEIGHT: f64 = 1.0;
+    const CORNER_WEIGHT: f64 = 2.0;
+
+    EMPTY_WEIGHT * empty_cells(pos).len() as f64
+        + SMOOTHNESS_WEIGHT * smoothness(pos)
+        + MONOTONICITY_WEIGHT * monotonicity(pos)
+        + CORNER_WEIGHT * corner_bonus(pos)
+}
+
+// Negative sum of absolute exponent differences between horizontally and vertically adjacent
+// tiles; boards with closer neighboring values are easier to merge and score higher (closer to 0).
+fn smoothness(pos: &Position) -> f64 {
+    let mut penalty = 0.0;
+
+    for row in 0..4usize {
+        for col in 0..4usize {
+            let e = pos.exponent_at(row, col) as f64;
+
+            if col + 1 < 4 {
+                penalty -= (e - pos.exponent_at(row, col + 1) as f64).abs();
+            }
+
+            if row + 1 < 4 {
+                penalty -= (e - pos.exponent_at(row + 1, col) as f64).abs();
+            }
+        }
+    }
+
+    penalty
+}
+
+// Rewards rows and columns whose exponents are sorted (ascending or descending); this keeps
+// large tiles accumulating along an edge instead of scattering.
+fn monotonicity(pos: &Position) -> f64 {
+    let mut score = 0.0;
+
+    for row in 0..4usize {
+        let line: Vec<f64> = (0..4).map(|col| pos.exponent_at(row, col) as f64).collect();
+        score += monotonic_line_score(&line);
+    }
+
+    for col in 0..4usize {
+        let line: Vec<f64> = (0..4).map(|row| pos.exponent_at(row, col) as f64).collect();
+        score += monotonic_line_score(&line);
+    }
+
+    score
+}
+
+fn monotonic_line_score(line: &[f64]) -> f64 {
+    let mut increasing = 0.0;
+    let mut decreasing = 0.0;
+
+    for pair in line.windows(2) {
+        let diff = pair[1] - pair[0];
+
+        if diff > 0.0 {
+            increasing += diff;
+        } else {
+            decreasing -= diff;
+        }
+    }
+
+    -increasing.min(decreasing)
+}
+
+// Bonus for keeping the largest tile in a corner, where it's least likely to get boxed in.
+fn corner_bonus(pos: &Position) -> f64 {
+    let max_exponent = (0..4)
+        .flat_map(|row| (0..4).map(move |col| pos.exponent_at(row, col)))
+        .max()
+        .unwrap_or(0);
+
+    let corners = [(0, 0), (0, 3), (3, 0), (3, 3)];
+
+    if corners.iter().any(|&(row, col)| pos.exponent_at(row, col) == max_exponent) {
+        max_exponent as f64
+    } else {
+        0.0
+    }
+}